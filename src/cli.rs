@@ -0,0 +1,102 @@
+use clap::{Parser, ValueEnum};
+
+#[derive(ValueEnum, Debug, Copy, Clone)]
+pub(crate) enum OnBusyUpdate {
+    Signal,
+    Queue,
+    DoNothing,
+}
+
+#[derive(ValueEnum, Debug, Copy, Clone)]
+#[clap(rename_all = "verbatim")]
+pub(crate) enum ChildSignal {
+    SIGHUP,
+    SIGINT,
+    SIGQUIT,
+    SIGTERM,
+    SIGKILL,
+    SIGUSR1,
+    SIGUSR2,
+}
+
+#[derive(Parser, Debug)]
+#[command(author, version, about)]
+pub(crate) struct Cli {
+    /// Set the timeout between detected change and command execution, defaults to 100ms
+    #[clap(long, short, default_value = "100")]
+    pub(crate) debounce: u64,
+    /// Clear screen before running command
+    #[clap(long, short = 'L')]
+    pub(crate) clear: bool,
+
+    /// Ignore paths matching the pattern
+    #[clap(long, short)]
+    pub(crate) ignore: Vec<String>,
+
+    /// Only watch paths with the given file extension
+    #[clap(long, short, value_delimiter(','))]
+    pub(crate) extensions: Vec<String>,
+
+    /// Select the behaviour to use when receiving events while the command is running
+    #[clap(long, default_value = "signal")]
+    pub(crate) on_busy_update: OnBusyUpdate,
+
+    /// The signal to send to the command if on-busy-update is set to signal
+    #[clap(long, default_value = "SIGTERM")]
+    pub(crate) signal: ChildSignal,
+
+    /// Do not use the default ignore globs
+    #[clap(long)]
+    pub(crate) no_default_ignore: bool,
+
+    #[clap(long)]
+    pub(crate) no_global_ignore: bool,
+
+    /// Skip auto-loading of the project `.gitignore`
+    #[clap(long)]
+    pub(crate) no_project_ignore: bool,
+
+    /// Disable both `.gitignore` and `.ignore` loading
+    #[clap(long)]
+    pub(crate) no_ignore: bool,
+
+    /// Use a polling watcher instead of native OS filesystem events. Needed on NFS, SMB, and
+    /// many Docker/VM bind mounts, where native events never fire.
+    #[clap(long)]
+    pub(crate) poll: bool,
+
+    /// Interval in milliseconds between polls when --poll is set
+    #[clap(long, default_value = "1000")]
+    pub(crate) poll_interval: u64,
+
+    /// How long to wait after sending --signal before escalating to SIGKILL on restart
+    #[clap(long, default_value = "5000")]
+    pub(crate) stop_timeout: u64,
+
+    /// Trigger a restart on demand by sending this signal to watchexec-simple, even if no
+    /// watched file changed. Useful when the command depends on external state the watcher
+    /// can't see. SIGINT is not allowed here, since it already terminates watchexec-simple.
+    #[clap(long)]
+    pub(crate) restart_signal: Option<ChildSignal>,
+
+    /// Also trigger a restart when an empty line, or a line holding just `r`, is entered on
+    /// stdin (Enter, or `r` then Enter). The watched command
+    /// inherits the same stdin, so don't combine this with a command that reads its own
+    /// input (REPLs, interactive installers, prompts) — both will race for keystrokes.
+    #[clap(long)]
+    pub(crate) restart_on_keypress: bool,
+
+    #[clap(default_value = ".")]
+    pub(crate) paths: Vec<String>,
+
+    #[clap(last(true), required(true), num_args(1..))]
+    pub(crate) command: Vec<String>,
+
+    /// Exec the command directly instead of running it through a shell. Disables pipelines,
+    /// `&&`, globs, and env-var expansion in the command.
+    #[clap(long, short = 'n')]
+    pub(crate) no_shell: bool,
+
+    #[clap(long, short, global = true)]
+    pub(crate) verbose: bool,
+}
@@ -0,0 +1,224 @@
+use std::path::PathBuf;
+use std::time::Duration;
+
+use clap::Parser;
+use command_group::Signal;
+use glob::Pattern;
+use ignore::gitignore::Gitignore;
+
+use crate::cli::{Cli, ChildSignal, OnBusyUpdate};
+use crate::err;
+use crate::error::Error;
+use crate::filter::{find_project_gitignore, find_project_ignore_file, Filter};
+
+#[derive(PartialEq, Eq, Debug, Copy, Clone)]
+pub enum BusyAction {
+    Restart,
+    DoNothing,
+    Queue,
+}
+
+fn to_signal(signal: ChildSignal) -> Signal {
+    match signal {
+        ChildSignal::SIGHUP => Signal::SIGHUP,
+        ChildSignal::SIGINT => Signal::SIGINT,
+        ChildSignal::SIGQUIT => Signal::SIGQUIT,
+        ChildSignal::SIGTERM => Signal::SIGTERM,
+        ChildSignal::SIGKILL => Signal::SIGKILL,
+        ChildSignal::SIGUSR1 => Signal::SIGUSR1,
+        ChildSignal::SIGUSR2 => Signal::SIGUSR2,
+    }
+}
+
+/// Everything [`crate::run`] needs to watch paths and drive the restart loop.
+pub struct Config {
+    pub debounce: u64,
+    pub strategy: BusyAction,
+    pub signal: Signal,
+    pub filter: Filter,
+    pub paths: Vec<PathBuf>,
+    pub command: Vec<String>,
+    pub no_shell: bool,
+    pub clear: bool,
+    pub poll: bool,
+    pub poll_interval: u64,
+    pub stop_timeout: Duration,
+    /// Send this signal to trigger a restart on demand, independent of watched files.
+    pub restart_signal: Option<Signal>,
+    /// Also trigger a restart when an empty line, or a line holding just `r`, is entered on stdin.
+    pub restart_on_keypress: bool,
+    pub verbose: bool,
+}
+
+impl Config {
+    /// Build a [`Config`] by parsing command-line style arguments, mirroring
+    /// `clap::Parser::try_parse_from`. This lets callers (tests, embedders) drive the whole
+    /// pipeline without a real process.
+    pub fn from_args<I, T>(argv: I) -> Result<Config, Error>
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<std::ffi::OsString> + Clone,
+    {
+        let cli = Cli::try_parse_from(argv).map_err(|e| err!("{}", e))?;
+        Config::from_cli(cli)
+    }
+
+    fn from_cli(cli: Cli) -> Result<Config, Error> {
+        if let Some(ChildSignal::SIGINT) = cli.restart_signal {
+            return Err(err!("--restart-signal SIGINT is not allowed: SIGINT already terminates watchexec-simple"));
+        }
+
+        let signal = to_signal(cli.signal);
+        let restart_signal = cli.restart_signal.map(to_signal);
+
+        let mut ignore_globs = cli.ignore.iter()
+            .map(|s| {
+                let mut s = s.to_string();
+                s += "*";
+                Pattern::new(&s)
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| err!("Invalid ignore glob: {}", e))?;
+
+        if !cli.no_default_ignore {
+            ignore_globs.push(Pattern::new("*~")
+                .map_err(|e| err!("Invalid ignore glob: {}", e))?);
+            ignore_globs.push(Pattern::new("**/.DS_Store")
+                .map_err(|e| err!("Invalid ignore glob: {}", e))?);
+            ignore_globs.push(Pattern::new(".git/*")
+                .map_err(|e| err!("Invalid ignore glob: {}", e))?);
+        }
+
+        let strategy = match cli.on_busy_update {
+            OnBusyUpdate::Signal => BusyAction::Restart,
+            OnBusyUpdate::Queue => BusyAction::Queue,
+            OnBusyUpdate::DoNothing => BusyAction::DoNothing,
+        };
+
+        let gitignore = if cli.no_project_ignore || cli.no_ignore {
+            None
+        } else {
+            find_project_gitignore()
+        };
+
+        let global_gitignore = if cli.no_global_ignore {
+            None
+        } else {
+            let (g, _) = Gitignore::global();
+            Some(g)
+        };
+
+        let ignore_file = if cli.no_ignore {
+            None
+        } else {
+            find_project_ignore_file(".ignore")
+        };
+
+        let mut paths = Vec::new();
+        let mut watched_files = Vec::new();
+        for s in cli.paths.iter() {
+            let p = std::fs::canonicalize(s).map_err(|e| err!("{}: {}", s, e))?;
+            if !p.is_dir() {
+                watched_files.push(p.clone());
+            }
+            paths.push(p);
+        }
+
+        let working_dir = std::env::current_dir().map_err(|e| err!("{}", e))?;
+
+        let filter = Filter {
+            working_dir,
+            watched_files,
+            extensions: cli.extensions,
+            gitignore,
+            global_gitignore,
+            ignore_file,
+            ignore_globs,
+        };
+
+        Ok(Config {
+            debounce: cli.debounce,
+            strategy,
+            signal,
+            filter,
+            paths,
+            command: cli.command,
+            no_shell: cli.no_shell,
+            clear: cli.clear,
+            poll: cli.poll,
+            poll_interval: cli.poll_interval,
+            stop_timeout: Duration::from_millis(cli.stop_timeout),
+            restart_signal,
+            restart_on_keypress: cli.restart_on_keypress,
+            verbose: cli.verbose,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_args_drives_the_whole_pipeline_without_a_real_process() {
+        let config = Config::from_args([
+            "watchexec-simple",
+            "--signal", "SIGKILL",
+            "--stop-timeout", "250",
+            "--restart-signal", "SIGUSR2",
+            "--restart-on-keypress",
+            "--no-shell",
+            "--",
+            "true",
+        ]).unwrap();
+
+        assert_eq!(config.signal, Signal::SIGKILL);
+        assert_eq!(config.stop_timeout, Duration::from_millis(250));
+        assert_eq!(config.restart_signal, Some(Signal::SIGUSR2));
+        assert!(config.restart_on_keypress);
+        assert!(config.no_shell);
+        assert_eq!(config.command, vec!["true".to_string()]);
+    }
+
+    #[test]
+    fn from_args_defaults_leave_manual_restart_disabled() {
+        let config = Config::from_args(["watchexec-simple", "--", "true"]).unwrap();
+
+        assert_eq!(config.restart_signal, None);
+        assert!(!config.restart_on_keypress);
+        assert_eq!(config.strategy, BusyAction::Restart);
+    }
+
+    #[test]
+    fn from_args_rejects_sigint_as_the_restart_signal() {
+        let result = Config::from_args([
+            "watchexec-simple",
+            "--restart-signal", "SIGINT",
+            "--",
+            "true",
+        ]);
+
+        assert!(result.is_err(), "SIGINT already terminates watchexec-simple and must not double as the restart signal");
+    }
+
+    #[test]
+    fn from_args_wires_up_poll_flags() {
+        let config = Config::from_args([
+            "watchexec-simple",
+            "--poll",
+            "--poll-interval", "250",
+            "--",
+            "true",
+        ]).unwrap();
+
+        assert!(config.poll);
+        assert_eq!(config.poll_interval, 250);
+    }
+
+    #[test]
+    fn from_args_defaults_leave_polling_disabled() {
+        let config = Config::from_args(["watchexec-simple", "--", "true"]).unwrap();
+
+        assert!(!config.poll);
+    }
+}
@@ -39,6 +39,11 @@ pub fn handle_event(
             return false;
         }
     }
+    if let Some(ignore_file) = &filter.ignore_file {
+        if ignore_file.matched_path_or_any_parents(&p.to_string_lossy().as_ref(), p.is_dir()).is_ignore() {
+            return false;
+        }
+    }
     true
 }
 
@@ -51,15 +56,45 @@ pub struct Filter {
     pub extensions: Vec<String>,
     pub gitignore: Option<Gitignore>,
     pub global_gitignore: Option<Gitignore>,
+    /// Ripgrep/fd-style `.ignore` file, loaded the same way as `gitignore` but kept separate
+    /// so it isn't tied to VCS state.
+    pub ignore_file: Option<Gitignore>,
     pub ignore_globs: Vec<Pattern>,
 }
 
+impl Filter {
+    pub fn new() -> Filter {
+        Filter {
+            working_dir: PathBuf::new(),
+            watched_files: Vec::new(),
+            extensions: Vec::new(),
+            gitignore: None,
+            global_gitignore: None,
+            ignore_file: None,
+            ignore_globs: Vec::new(),
+        }
+    }
+}
+
+impl Default for Filter {
+    fn default() -> Self {
+        Filter::new()
+    }
+}
+
 pub fn find_project_gitignore() -> Option<Gitignore> {
+    find_project_ignore_file(".gitignore")
+}
+
+/// Look for `name` in the current directory. Parent directories are not searched: the
+/// lookup starts from the relative path `.`, whose only parent is the empty path, which
+/// names the same directory.
+pub fn find_project_ignore_file(name: &str) -> Option<Gitignore> {
     let mut path = PathBuf::from(".");
     loop {
-        let gitignore_path = path.join(".gitignore");
-        if gitignore_path.exists() {
-            let (ignore, _) = Gitignore::new(gitignore_path);
+        let ignore_path = path.join(name);
+        if ignore_path.exists() {
+            let (ignore, _) = Gitignore::new(ignore_path);
             return Some(ignore);
         }
         if path.parent().is_none() || path.join(".git").exists() {
@@ -78,11 +113,11 @@ mod tests {
     #[test]
     fn test_extension() {
         let mut filter = Filter::new();
-        filter.extensions.push("rs");
+        filter.extensions.push("rs".to_string());
         let p = PathBuf::from("/Users/debug/.fingerprint/server2-66aa47d134ef7589/invoked.timestamp");
         assert_eq!(handle_event(&p, &filter), false, ".timestamp ignored when watching .rs files");
 
-        filter.extensions.push("ts");
+        filter.extensions.push("ts".to_string());
         let p = PathBuf::from("foo/bar.d.ts");
         assert_eq!(handle_event(&p, &filter), false, "handle two file extensions");
     }
@@ -115,4 +150,16 @@ mod tests {
         assert_eq!(handle_event(&path, &filter), false, "ignore globs should match");
     }
 
+    #[test]
+    fn test_ignore_file() {
+        let mut filter = Filter::new();
+        let root = PathBuf::from("/Users/kurt/work/server/");
+        let mut ignore = GitignoreBuilder::new(&root);
+            ignore.add_line(Some(root), "/vendor").unwrap();
+        let ignore = ignore.build().unwrap();
+        filter.ignore_file = Some(ignore);
+        let p = PathBuf::from("/Users/kurt/work/server/vendor/crate/lib.rs");
+        assert_eq!(handle_event(&p, &filter), false, ".ignore file should be consulted like .gitignore");
+    }
+
 }
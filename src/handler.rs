@@ -0,0 +1,141 @@
+use std::process::Command;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use command_group::{CommandGroup, GroupChild, Signal, UnixChildExt};
+use tracing::debug;
+
+use crate::err;
+use crate::error::Error;
+
+/// The resolved program and arguments [`run`](crate::run) will spawn on each (re)start, after
+/// shell-wrapping has already been applied.
+pub struct CommandSpec {
+    pub program: String,
+    pub args: Vec<String>,
+}
+
+impl CommandSpec {
+    pub(crate) fn new(command: &[String], no_shell: bool) -> CommandSpec {
+        if no_shell {
+            CommandSpec {
+                program: command[0].clone(),
+                args: command[1..].to_vec(),
+            }
+        } else if cfg!(windows) {
+            CommandSpec {
+                program: "cmd.exe".to_string(),
+                args: vec!["/C".to_string(), command.join(" ")],
+            }
+        } else {
+            CommandSpec {
+                program: "sh".to_string(),
+                args: vec!["-c".to_string(), command.join(" ")],
+            }
+        }
+    }
+
+    fn spawn(&self) -> Result<GroupChild, Error> {
+        Command::new(&self.program)
+            .args(&self.args)
+            .group_spawn()
+            .map_err(|_| err!("{}: command not found", self.program))
+    }
+}
+
+/// Customizes how [`crate::run`] spawns and stops the watched command, e.g. to embed the
+/// restart loop in another program and call back into Rust instead of only spawning a
+/// subprocess. The default implementations reproduce watchexec-simple's built-in behaviour.
+pub trait Handler {
+    /// Called the first time the command needs to run, on startup.
+    ///
+    /// Not to be confused with a user-triggered manual restart (`--restart-signal` /
+    /// `--restart-on-keypress`), which dispatches through [`Handler::on_change_run`] like any
+    /// other restart.
+    fn on_initial_run(&mut self, command: &CommandSpec) -> Result<GroupChild, Error> {
+        command.spawn()
+    }
+
+    /// Called to spawn the replacement child on every restart after the first — whether
+    /// triggered by a watched file changing, `--restart-signal`, or `--restart-on-keypress`.
+    fn on_change_run(&mut self, command: &CommandSpec) -> Result<GroupChild, Error> {
+        command.spawn()
+    }
+
+    /// Called to stop the previous child before `on_change_run` spawns its replacement.
+    ///
+    /// Sends `signal` and waits up to `stop_timeout` for the child to exit; if it's still
+    /// alive by then (e.g. it ignores the configured signal) escalates to `SIGKILL`.
+    fn on_restart(&mut self, child: &mut GroupChild, signal: Signal, stop_timeout: Duration) -> Result<(), Error> {
+        child.signal(signal)
+            .unwrap_or_else(|e| debug!("Failed to signal children: {}", e));
+
+        let deadline = Instant::now() + stop_timeout;
+        loop {
+            if child.try_wait().map_err(|e| err!("{}", e))?.is_some() {
+                return Ok(());
+            }
+            if Instant::now() >= deadline {
+                break;
+            }
+            thread::sleep(Duration::from_millis(20));
+        }
+
+        debug!("Process did not exit within stop-timeout, sending SIGKILL");
+        child.signal(Signal::SIGKILL)
+            .unwrap_or_else(|e| debug!("Failed to SIGKILL children: {}", e));
+        child.wait().map_err(|e| err!("{}", e))?;
+        Ok(())
+    }
+}
+
+/// The [`Handler`] used by the `watchexec-simple` binary: spawns and signals the child
+/// process through the trait's default implementations.
+#[derive(Default)]
+pub struct DefaultHandler;
+
+impl Handler for DefaultHandler {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct FakeHandler;
+    impl Handler for FakeHandler {}
+
+    #[test]
+    #[cfg(not(windows))]
+    fn new_wraps_the_command_in_a_shell_by_default() {
+        let command = vec!["cargo".to_string(), "build".to_string(), "&&".to_string(), "./run".to_string()];
+        let spec = CommandSpec::new(&command, false);
+
+        assert_eq!(spec.program, "sh");
+        assert_eq!(spec.args, vec!["-c".to_string(), "cargo build && ./run".to_string()]);
+    }
+
+    #[test]
+    fn new_execs_directly_with_no_shell() {
+        let command = vec!["cargo".to_string(), "build".to_string(), "--release".to_string()];
+        let spec = CommandSpec::new(&command, true);
+
+        assert_eq!(spec.program, "cargo");
+        assert_eq!(spec.args, vec!["build".to_string(), "--release".to_string()]);
+    }
+
+    #[test]
+    fn on_restart_escalates_to_sigkill_when_the_child_ignores_the_signal() {
+        let command = CommandSpec {
+            program: "sh".to_string(),
+            args: vec!["-c".to_string(), "trap '' TERM; sleep 5".to_string()],
+        };
+        let mut child = command.spawn().unwrap();
+        let mut handler = FakeHandler;
+
+        let start = Instant::now();
+        handler.on_restart(&mut child, Signal::SIGTERM, Duration::from_millis(200)).unwrap();
+
+        assert!(start.elapsed() < Duration::from_secs(2),
+            "should escalate to SIGKILL well before the child's 5s sleep finishes");
+        assert!(child.try_wait().unwrap().is_some(), "child should be reaped after on_restart");
+    }
+}
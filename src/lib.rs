@@ -0,0 +1,13 @@
+#![forbid(unsafe_code)]
+
+pub mod error;
+pub mod filter;
+
+mod cli;
+mod config;
+mod handler;
+mod run;
+
+pub use config::{BusyAction, Config};
+pub use handler::{CommandSpec, DefaultHandler, Handler};
+pub use run::run;
@@ -0,0 +1,218 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{channel, RecvTimeoutError};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use command_group::{GroupChild, Signal, UnixChildExt};
+use notify::{watcher, DebouncedEvent, PollWatcher, RecursiveMode, Watcher};
+use tracing::{debug, warn};
+
+use crate::config::{BusyAction, Config};
+use crate::err;
+use crate::error::Error;
+use crate::filter;
+use crate::handler::{CommandSpec, Handler};
+
+#[derive(PartialEq, Eq)]
+enum Status {
+    RestartProcess,
+    Waiting,
+    RestartTriggered(Instant),
+}
+
+/// Watch `config`'s paths and drive `handler` through the restart state machine until the
+/// process is asked to terminate.
+pub fn run(config: Config, mut handler: impl Handler) -> Result<(), Error> {
+    let command = CommandSpec::new(&config.command, config.no_shell);
+
+    let (sender, receiver) = channel();
+    // Not sure why, but the built-in debouncing seems to cause us to drop tons of events that should
+    // be handled. Instead, we implement our own debouncing.
+    let mut watcher: Box<dyn Watcher> = if config.poll {
+        Box::new(PollWatcher::new(sender, Duration::from_millis(config.poll_interval))
+            .map_err(|e| err!("{}", e))?)
+    } else {
+        Box::new(watcher(sender, Duration::from_millis(0)).map_err(|e| err!("{}", e))?)
+    };
+
+    for p in &config.paths {
+        if p.is_dir() {
+            debug!("{}: Watching directory", p.display());
+            watcher.watch(p, RecursiveMode::Recursive).map_err(|e| err!("{}", e))?;
+        } else {
+            debug!("{}: Watching file", p.display());
+            watcher.watch(p, RecursiveMode::NonRecursive).map_err(|e| err!("{}", e))?;
+        }
+    }
+
+    let mut status = Status::RestartProcess;
+    let mut child: Option<GroupChild> = None;
+    let mut first_run = true;
+
+    let terminate_signal = Arc::new(AtomicBool::new(false));
+    let child_signal = Arc::new(AtomicBool::new(false));
+    let manual_restart = Arc::new(AtomicBool::new(false));
+
+    signal_hook::flag::register(signal_hook::consts::SIGINT, terminate_signal.clone())
+        .map_err(|e| err!("{}", e))?;
+    signal_hook::flag::register(signal_hook::consts::SIGCHLD, child_signal.clone())
+        .map_err(|e| err!("{}", e))?;
+    if let Some(signal) = config.restart_signal {
+        register_manual_restart_signal(signal, &manual_restart)?;
+    }
+    if config.restart_on_keypress {
+        warn!("--restart-on-keypress reads the watched command's stdin; commands that read \
+               their own input will race it for keystrokes");
+        spawn_keypress_listener(manual_restart.clone());
+    }
+
+    loop {
+
+        // a manual trigger (signal or keypress) forces a restart even with no file change
+        if manual_restart.swap(false, Ordering::Relaxed) {
+            debug!("Manual restart triggered.");
+            status = Status::RestartProcess;
+        }
+
+        // restart the process if necessary
+        if status == Status::RestartProcess {
+            status = Status::Waiting;
+
+            match config.strategy {
+                BusyAction::Restart => {
+                    if let Some(mut old_child) = child.take() {
+                        debug!("Waiting for process to exit...");
+                        handler.on_restart(&mut old_child, config.signal, config.stop_timeout)?;
+                        debug!("Exited");
+                    }
+                }
+                BusyAction::DoNothing => {
+                    if let Some(c) = child.as_mut() {
+                        if c.try_wait().map_err(|e| err!("{}", e))?.is_none() {
+                            continue;
+                        }
+                    }
+                }
+                BusyAction::Queue => {
+                    if let Some(c) = child.as_mut() {
+                        if c.try_wait().map_err(|e| err!("{}", e))?.is_none() {
+                            status = Status::RestartProcess;
+                            thread::sleep(Duration::from_millis(50));
+                            continue;
+                        }
+                    }
+                }
+            }
+            if config.clear {
+                clearscreen::clear().expect("failed to clear screen");
+            }
+            child = Some(if first_run {
+                first_run = false;
+                handler.on_initial_run(&command)?
+            } else {
+                handler.on_change_run(&command)?
+            });
+        }
+
+        // check if we've been asked to terminate
+        if terminate_signal.load(Ordering::Relaxed) {
+            if let Some(mut c) = child.take() {
+                let _ = c.signal(Signal::SIGINT);
+            }
+            std::process::exit(1);
+        }
+
+        // check if the child terminated via signal
+        // this is a hack to get around the fact that vite
+        // swallows SIGTERM and SIGINT
+        if let Some(c) = &mut child {
+            if let Ok(Some(_)) = c.try_wait() {
+                if child_signal.load(Ordering::Relaxed) {
+                    std::process::exit(130);
+                }
+            }
+        }
+
+        // check if we should trigger a restart based on a file change
+        match receiver.recv_timeout(Duration::from_millis(config.debounce)) {
+            Ok(event) => {
+                let w = match event {
+                    DebouncedEvent::NoticeWrite(w)
+                    | DebouncedEvent::Write(w)
+                    | DebouncedEvent::Chmod(w)
+                    => {
+                        w
+                    }
+                    _ => continue,
+                };
+
+                if !filter::handle_event(&w, &config.filter) {
+                    continue;
+                }
+                debug!("{}: File modified. Queuing restart.", w.display());
+                status = Status::RestartTriggered(Instant::now());
+            }
+            Err(e) => {
+                match e {
+                    RecvTimeoutError::Timeout => {
+                        if let Status::RestartTriggered(instant) = status {
+                            if instant.elapsed() > Duration::from_millis(config.debounce) {
+                                status = Status::RestartProcess;
+                            }
+                        }
+                    }
+                    RecvTimeoutError::Disconnected => {
+                        return Err(err!("watchexec disconected"));
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Read stdin line by line in a background thread, flipping `manual_restart` whenever the
+/// user enters an empty line or a line holding just `r`, so the main loop picks it up on its next iteration.
+///
+/// This shares stdin with the watched command (spawned without a `.stdin()` override), so it
+/// competes for keystrokes with any command that reads its own input.
+fn spawn_keypress_listener(manual_restart: Arc<AtomicBool>) {
+    use std::io::BufRead;
+
+    thread::spawn(move || {
+        let stdin = std::io::stdin();
+        for line in stdin.lock().lines() {
+            match line {
+                Ok(line) if line.is_empty() || line == "r" => {
+                    manual_restart.store(true, Ordering::Relaxed);
+                }
+                Ok(_) => {}
+                Err(_) => break,
+            }
+        }
+    });
+}
+
+/// Register `signal` so that receiving it sets `flag`, letting the main loop pick up a
+/// manual restart on its next iteration without touching any watched file.
+fn register_manual_restart_signal(signal: Signal, flag: &Arc<AtomicBool>) -> Result<(), Error> {
+    signal_hook::flag::register(signal as i32, flag.clone()).map_err(|e| err!("{}", e))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn manual_restart_signal_flips_the_flag() {
+        let flag = Arc::new(AtomicBool::new(false));
+        register_manual_restart_signal(Signal::SIGUSR2, &flag).unwrap();
+        assert!(!flag.load(Ordering::Relaxed));
+
+        signal_hook::low_level::raise(Signal::SIGUSR2 as i32).unwrap();
+        thread::sleep(Duration::from_millis(50));
+
+        assert!(flag.load(Ordering::Relaxed), "receiving the configured signal should flip the flag");
+    }
+}